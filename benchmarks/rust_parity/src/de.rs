@@ -1,12 +1,16 @@
-use std::{fmt::Display, marker::PhantomData};
+use std::{fmt::Display, io, marker::PhantomData};
 
 use num_bigint::Sign;
 use serde::{
-    de::{self, MapAccess, SeqAccess, Visitor},
+    de::{self, DeserializeOwned, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
     forward_to_deserialize_any, Deserialize,
 };
 
-use crate::format::{value, Error, Result, Value};
+use crate::{
+    format::{Error, Result, Value},
+    reader::Reader,
+    value_ref::ValueRef,
+};
 
 impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
@@ -14,20 +18,159 @@ impl de::Error for Error {
     }
 }
 
-pub struct Deserializer<'de> {
-    input: &'de [u8],
-    pending: Vec<Value>,
+/// A value pulled off a [`Source`]: either freshly allocated (from an
+/// `IoRead` stream, or staged internally by the deserializer itself), or
+/// borrowed straight out of a `SliceSource`'s input buffer. Keeping both
+/// representations around lets `visit_value` call `visit_borrowed_str`/
+/// `visit_borrowed_bytes` whenever the data actually supports it, instead of
+/// always paying for an owned copy.
+pub enum SourceValue<'de> {
+    Owned(Value),
+    Borrowed(ValueRef<'de>),
+}
+
+/// Where [`Deserializer`] pulls its next value from: a fully-buffered slice
+/// ([`SliceSource`]), which can hand back borrowed data, or an incremental
+/// `std::io::Read` stream ([`IoRead`]), which can only ever produce owned
+/// data.
+pub trait Source<'de> {
+    /// Parse the next value off the source, or `Ok(None)` at end of input.
+    fn next_value(&mut self) -> Result<Option<SourceValue<'de>>>;
 }
 
-impl<'de> Deserializer<'de> {
+/// A [`Source`] that borrows a complete, already-buffered byte slice.
+pub struct SliceSource<'de>(&'de [u8]);
+
+impl<'de> SliceSource<'de> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// How many bytes are left unconsumed, for reporting a
+    /// [`Error::TrailingGarbage`] offset relative to the original input.
+    fn remaining_len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The unconsumed remainder of the input.
+    fn remaining(&self) -> &'de [u8] {
+        self.0
+    }
+}
+
+impl<'de> Source<'de> for SliceSource<'de> {
+    fn next_value(&mut self) -> Result<Option<SourceValue<'de>>> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        let (remaining, parsed) = ValueRef::parse(self.0)?;
+        self.0 = remaining;
+        Ok(Some(SourceValue::Borrowed(parsed)))
+    }
+}
+
+/// A [`Source`] that incrementally decodes values off any `std::io::Read`,
+/// via [`Reader`], without buffering the whole stream into memory first.
+/// Since nothing about an `io::Read` stream outlives the call that reads it,
+/// every value it produces is owned.
+pub struct IoRead<R> {
+    input: R,
+}
+
+impl<R: io::Read> IoRead<R> {
+    pub fn new(input: R) -> Self {
+        IoRead { input }
+    }
+}
+
+impl<'de, R: io::Read> Source<'de> for IoRead<R> {
+    fn next_value(&mut self) -> Result<Option<SourceValue<'de>>> {
+        Ok(Reader::new(&mut self.input)
+            .try_read_value()?
+            .map(SourceValue::Owned))
+    }
+}
+
+pub struct Deserializer<'de, S: Source<'de>> {
+    source: S,
+    pending: Vec<SourceValue<'de>>,
+    require_label: bool,
+}
+
+impl<'de> Deserializer<'de, SliceSource<'de>> {
     pub fn from_bytes(input: &'de [u8]) -> Self {
+        Deserializer::from_source(SliceSource(input))
+    }
+}
+
+impl<'de, R: io::Read> Deserializer<'de, IoRead<R>> {
+    pub fn from_reader(input: R) -> Self {
+        Deserializer::from_source(IoRead::new(input))
+    }
+}
+
+impl<'de, S: Source<'de>> Deserializer<'de, S> {
+    pub fn from_source(source: S) -> Self {
         Deserializer {
-            input,
+            source,
             pending: vec![],
+            require_label: false,
         }
     }
 
-    fn visit_value<V>(&mut self, visitor: V, value: Value) -> Result<V::Value>
+    /// When set, deserializing a struct or enum checks that the record's
+    /// label symbol matches the target type's name (the variant tag is
+    /// already validated, since an unrecognized variant symbol fails to
+    /// deserialize into serde's generated identifier enum regardless of this
+    /// setting), returning an error on mismatch. Off by default: as
+    /// `test_struct_from_record` documents, the label is otherwise ignored.
+    pub fn require_label(mut self, require: bool) -> Self {
+        self.require_label = require;
+        self
+    }
+
+    fn check_label(&self, label: &Value, expected: &str) -> Result<()> {
+        match label {
+            Value::Symbol(actual) if actual == expected => Ok(()),
+            _ if self.require_label => Err(Error::message(format!(
+                "expected record label {:?}, found {:?}",
+                expected, label
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_label_ref(&self, label: &ValueRef<'de>, expected: &str) -> Result<()> {
+        match label {
+            ValueRef::Symbol(actual) if *actual == expected => Ok(()),
+            _ if self.require_label => Err(Error::message(format!(
+                "expected record label {:?}, found {:?}",
+                expected, label
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Take the next value: one already staged in `pending`, or the next one
+    /// parsed off the source.
+    fn take_value(&mut self) -> Result<SourceValue<'de>> {
+        match self.pending.pop() {
+            Some(next) => Ok(next),
+            None => self.source.next_value()?.ok_or(Error::Eof),
+        }
+    }
+
+    fn visit_value<V>(&mut self, visitor: V, value: SourceValue<'de>) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match value {
+            SourceValue::Owned(v) => self.visit_owned(visitor, v),
+            SourceValue::Borrowed(v) => self.visit_borrowed(visitor, v),
+        }
+    }
+
+    fn visit_owned<V>(&mut self, visitor: V, value: Value) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
@@ -56,11 +199,16 @@ impl<'de> Deserializer<'de> {
             }
             Value::Dictionary(mut v) => {
                 v.reverse();
-                visitor.visit_map(DictionaryAccessor::new(self, v))
+                let items = v
+                    .into_iter()
+                    .map(|(k, v)| (SourceValue::Owned(k), SourceValue::Owned(v)))
+                    .collect();
+                visitor.visit_map(DictionaryAccessor::new(self, items))
             }
             Value::Sequence(mut v) => {
                 v.reverse();
-                visitor.visit_seq(SequenceAccessor::new(self, v))
+                let items = v.into_iter().map(SourceValue::Owned).collect();
+                visitor.visit_seq(SequenceAccessor::new(self, items))
             }
             Value::Record {
                 label: _,
@@ -70,77 +218,404 @@ impl<'de> Deserializer<'de> {
                     // Empty fields are either a "nil" unit or a unit struct
                     return visitor.visit_unit();
                 }
-                if let &Value::Symbol(ref _variant) = &fields[0] {
+                if let Value::Symbol(_) = &fields[0] {
                     // Leading symbol indicates an enum variant.
                     // See Serializer::serialize_*_variant methods.
-                    todo!()
+                    let fields = fields.into_iter().map(SourceValue::Owned).collect();
+                    visitor.visit_enum(VariantDeserializer { de: self, fields })
                 } else if fields.len() == 1 {
                     // A single value is likely some kind of struct (newtype or otherwise).
                     // Just unwrap it.
-                    self.visit_value(visitor, fields.pop().unwrap())
+                    self.visit_owned(visitor, fields.pop().unwrap())
                 } else {
-                    self.pending.push(Value::Sequence(fields));
+                    self.pending
+                        .push(SourceValue::Owned(Value::Sequence(fields)));
                     visitor.visit_newtype_struct(self)
                 }
             }
             Value::Set(mut v) => {
                 v.reverse();
-                visitor.visit_seq(SequenceAccessor::new(self, v))
+                let items = v.into_iter().map(SourceValue::Owned).collect();
+                visitor.visit_seq(SequenceAccessor::new(self, items))
+            }
+            Value::Embedded(mut v) => {
+                v.reverse();
+                visitor.visit_seq(BinaryDeserializer::new(v))
+            }
+        }
+    }
+
+    fn visit_borrowed<V>(&mut self, visitor: V, value: ValueRef<'de>) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match value {
+            ValueRef::Boolean(v) => visitor.visit_bool(v),
+            ValueRef::Float(v) => visitor.visit_f32(v),
+            ValueRef::Double(v) => visitor.visit_f64(v),
+            ValueRef::Integer(v) => {
+                if v.sign() == Sign::Minus {
+                    visitor.visit_i64(i64::try_from(&v).map_err(Error::message)?)
+                } else {
+                    visitor.visit_u64(u64::try_from(&v).map_err(Error::message)?)
+                }
+            }
+            ValueRef::Binary(b) => {
+                let mut v = b.to_vec();
+                v.reverse();
+                visitor.visit_seq(BinaryDeserializer::new(v))
+            }
+            ValueRef::String(s) => visitor.visit_borrowed_str(s),
+            ValueRef::Symbol(s) => {
+                if s == "nil" {
+                    visitor.visit_unit()
+                } else {
+                    visitor.visit_borrowed_str(s)
+                }
+            }
+            ValueRef::Dictionary(mut v) => {
+                v.reverse();
+                let items = v
+                    .into_iter()
+                    .map(|(k, v)| (SourceValue::Borrowed(k), SourceValue::Borrowed(v)))
+                    .collect();
+                visitor.visit_map(DictionaryAccessor::new(self, items))
+            }
+            ValueRef::Sequence(mut v) => {
+                v.reverse();
+                let items = v.into_iter().map(SourceValue::Borrowed).collect();
+                visitor.visit_seq(SequenceAccessor::new(self, items))
+            }
+            ValueRef::Record {
+                label: _,
+                mut fields,
+            } => {
+                if fields.is_empty() {
+                    return visitor.visit_unit();
+                }
+                if let ValueRef::Symbol(_) = &fields[0] {
+                    let fields = fields.into_iter().map(SourceValue::Borrowed).collect();
+                    visitor.visit_enum(VariantDeserializer { de: self, fields })
+                } else if fields.len() == 1 {
+                    self.visit_borrowed(visitor, fields.pop().unwrap())
+                } else {
+                    self.pending
+                        .push(SourceValue::Borrowed(ValueRef::Sequence(fields)));
+                    visitor.visit_newtype_struct(self)
+                }
+            }
+            ValueRef::Set(mut v) => {
+                v.reverse();
+                let items = v.into_iter().map(SourceValue::Borrowed).collect();
+                visitor.visit_seq(SequenceAccessor::new(self, items))
+            }
+            ValueRef::Embedded(b) => {
+                let mut v = b.to_vec();
+                v.reverse();
+                visitor.visit_seq(BinaryDeserializer::new(v))
             }
         }
     }
 }
 
-/// Deserialize a rust value from a byte-slice containing syrup-formatted data.
+/// Deserialize a rust value from a byte-slice containing syrup-formatted
+/// data. Strings, symbols and identifiers borrowed into `&'de str`/`&'de
+/// [u8]` fields are read straight out of `b` without copying.
 pub fn try_from_bytes<'a, T>(b: &'a [u8]) -> Result<T>
 where
     T: Deserialize<'a>,
 {
+    let total_len = b.len();
     let mut deserializer = Deserializer::from_bytes(b);
     let t = T::deserialize(&mut deserializer)?;
-    if deserializer.input.is_empty() {
+    if deserializer.source.is_empty() {
         Ok(t)
     } else {
-        Err(Error::Message("trailing values".to_string()))
+        Err(Error::TrailingGarbage {
+            offset: total_len - deserializer.source.remaining_len(),
+        })
+    }
+}
+
+/// Deserialize a single value from the front of `b`, returning it alongside
+/// the unconsumed remainder. Unlike [`try_from_bytes`], leftover bytes are
+/// not an error, so a caller can decode a stream of concatenated syrup
+/// values (framed messages, length-prefixed log entries) one at a time.
+pub fn try_from_bytes_partial<'a, T>(b: &'a [u8]) -> Result<(T, &'a [u8])>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_bytes(b);
+    let t = T::deserialize(&mut deserializer)?;
+    Ok((t, deserializer.source.remaining()))
+}
+
+/// Iterate over a slice of concatenated syrup-formatted values, decoding one
+/// `T` at a time via [`try_from_bytes_partial`] until the slice is empty.
+pub fn iter_from_bytes<T>(b: &[u8]) -> FromBytesIter<'_, T> {
+    FromBytesIter {
+        remaining: b,
+        _marker: PhantomData,
     }
 }
 
+/// Iterator returned by [`iter_from_bytes`]. Stops (yielding no further
+/// items) once the remaining slice is empty or a value fails to decode.
+pub struct FromBytesIter<'a, T> {
+    remaining: &'a [u8],
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: Deserialize<'a>> Iterator for FromBytesIter<'a, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        match try_from_bytes_partial(self.remaining) {
+            Ok((value, rest)) => {
+                self.remaining = rest;
+                Some(Ok(value))
+            }
+            Err(e) => {
+                self.remaining = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Deserialize a rust value from a byte-slice containing syrup-formatted data.
+///
+/// Alias for [`try_from_bytes`], named to match the `from_slice` convention
+/// used by other serde-backed formats.
+pub fn from_slice<'a, T>(b: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    try_from_bytes(b)
+}
+
 /// Deserialize a rust value from a parsed representation of syrup-formatted data.
 pub fn from_value<'a, T>(v: Value) -> Result<T>
 where
     T: Deserialize<'a>,
 {
     let mut deserializer = Deserializer::from_bytes(&[]);
-    deserializer.pending.push(v);
+    deserializer.pending.push(SourceValue::Owned(v));
     let t = T::deserialize(&mut deserializer)?;
-    if deserializer.input.is_empty() {
+    if deserializer.source.is_empty() {
         Ok(t)
     } else {
-        Err(Error::Message("trailing values".to_string()))
+        Err(Error::TrailingGarbage {
+            offset: deserializer.source.remaining_len(),
+        })
     }
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+/// Deserialize a rust value by incrementally decoding syrup-formatted data
+/// off `reader`, without buffering the whole stream into memory first. Unlike
+/// [`try_from_bytes`], trailing data left on `reader` after `T` is decoded is
+/// not an error, so multiple values can be read in sequence from the same
+/// stream. Since nothing borrowed from the stream can outlive this call,
+/// `T` must be [`DeserializeOwned`].
+pub fn from_reader<T, R>(reader: R) -> Result<T>
+where
+    T: DeserializeOwned,
+    R: io::Read,
+{
+    let mut deserializer = Deserializer::from_reader(reader);
+    T::deserialize(&mut deserializer)
+}
+
+impl<'de, 'a, S: Source<'de>> de::Deserializer<'de> for &'a mut Deserializer<'de, S> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.pending.pop() {
-            Some(next) => self.visit_value(visitor, next),
-            None => {
-                let (remaining, parsed) = value(self.input)?;
-                self.input = remaining;
-                self.visit_value(visitor, parsed)
+        let value = self.take_value()?;
+        self.visit_value(visitor, value)
+    }
+
+    /// `&[u8]`/`ByteBuf`'s `Deserialize` impls call this (rather than
+    /// `deserialize_seq`, the way a plain `Vec<u8>` does), so this is the one
+    /// place a borrowed binary value must produce `visit_borrowed_bytes`
+    /// instead of the generic `visit_seq` dispatch `visit_value` otherwise
+    /// uses for [`Value::Binary`]/[`ValueRef::Binary`].
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.take_value()? {
+            SourceValue::Owned(Value::Binary(v) | Value::Embedded(v)) => visitor.visit_byte_buf(v),
+            SourceValue::Borrowed(ValueRef::Binary(b) | ValueRef::Embedded(b)) => {
+                visitor.visit_borrowed_bytes(b)
             }
+            other => self.visit_value(visitor, other),
         }
     }
 
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.take_value()? {
+            SourceValue::Owned(Value::Record { label, fields })
+                if matches!(fields.first(), Some(Value::Symbol(_))) =>
+            {
+                self.check_label(label.as_ref(), name)?;
+                let fields = fields.into_iter().map(SourceValue::Owned).collect();
+                visitor.visit_enum(VariantDeserializer { de: self, fields })
+            }
+            SourceValue::Borrowed(ValueRef::Record { label, fields })
+                if matches!(fields.first(), Some(ValueRef::Symbol(_))) =>
+            {
+                self.check_label_ref(&label, name)?;
+                let fields = fields.into_iter().map(SourceValue::Borrowed).collect();
+                visitor.visit_enum(VariantDeserializer { de: self, fields })
+            }
+            other => Err(Error::message(format!(
+                "expected an enum variant record, found {:?}",
+                match other {
+                    SourceValue::Owned(v) => format!("{:?}", v),
+                    SourceValue::Borrowed(v) => format!("{:?}", v),
+                }
+            ))),
+        }
+    }
+
+    /// `struct` is handled explicitly rather than via
+    /// `forward_to_deserialize_any!` so that, in [`Deserializer::require_label`]
+    /// mode, the record's label can be checked against `name` before the
+    /// value is otherwise decoded exactly as `deserialize_any` would.
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.take_value()?;
+        match &value {
+            SourceValue::Owned(Value::Record { label, .. }) => self.check_label(label, name)?,
+            SourceValue::Borrowed(ValueRef::Record { label, .. }) => {
+                self.check_label_ref(label, name)?
+            }
+            _ => {}
+        }
+        self.visit_value(visitor, value)
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        option unit unit_struct newtype_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
+
+/// Drives [`de::EnumAccess`]/[`de::VariantAccess`] off a record whose first
+/// field is the variant tag symbol and whose remaining fields are the
+/// variant's payload, mirroring `Serializer::serialize_*_variant`.
+struct VariantDeserializer<'a, 'de, S: Source<'de>> {
+    de: &'a mut Deserializer<'de, S>,
+    fields: Vec<SourceValue<'de>>,
+}
+
+impl<'de, 'a, S: Source<'de>> EnumAccess<'de> for VariantDeserializer<'a, 'de, S> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<T>(mut self, seed: T) -> Result<(T::Value, Self::Variant)>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let tag = self.fields.remove(0);
+        self.de.pending.push(tag);
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a, S: Source<'de>> VariantAccess<'de> for VariantDeserializer<'a, 'de, S> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        if self.fields.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::message("expected a unit variant"))
+        }
+    }
+
+    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.fields.len() != 1 {
+            return Err(Error::message("expected a newtype variant"));
+        }
+        self.de.pending.push(self.fields.remove(0));
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut fields = self.fields;
+        fields.reverse();
+        visitor.visit_seq(SequenceAccessor::new(self.de, fields))
+    }
+
+    fn struct_variant<V>(mut self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.fields.len() != 1 {
+            return Err(Error::message("expected a struct variant dictionary"));
+        }
+        match self.fields.remove(0) {
+            SourceValue::Owned(Value::Dictionary(mut entries)) => {
+                entries.reverse();
+                let items = entries
+                    .into_iter()
+                    .map(|(k, v)| (SourceValue::Owned(k), SourceValue::Owned(v)))
+                    .collect();
+                visitor.visit_map(DictionaryAccessor::new(self.de, items))
+            }
+            SourceValue::Borrowed(ValueRef::Dictionary(mut entries)) => {
+                entries.reverse();
+                let items = entries
+                    .into_iter()
+                    .map(|(k, v)| (SourceValue::Borrowed(k), SourceValue::Borrowed(v)))
+                    .collect();
+                visitor.visit_map(DictionaryAccessor::new(self.de, items))
+            }
+            other => Err(Error::message(format!(
+                "expected a struct variant dictionary, found {:?}",
+                match other {
+                    SourceValue::Owned(v) => format!("{:?}", v),
+                    SourceValue::Borrowed(v) => format!("{:?}", v),
+                }
+            ))),
+        }
     }
 }
 
@@ -199,18 +674,18 @@ impl<'de, 'a> SeqAccess<'de> for BinaryDeserializer<'de> {
     }
 }
 
-struct SequenceAccessor<'a, 'de: 'a> {
-    de: &'a mut Deserializer<'de>,
-    items: Vec<Value>,
+struct SequenceAccessor<'a, 'de, S: Source<'de>> {
+    de: &'a mut Deserializer<'de, S>,
+    items: Vec<SourceValue<'de>>,
 }
 
-impl<'a, 'de> SequenceAccessor<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>, items: Vec<Value>) -> Self {
+impl<'a, 'de, S: Source<'de>> SequenceAccessor<'a, 'de, S> {
+    fn new(de: &'a mut Deserializer<'de, S>, items: Vec<SourceValue<'de>>) -> Self {
         SequenceAccessor { de, items }
     }
 }
 
-impl<'de, 'a> SeqAccess<'de> for SequenceAccessor<'a, 'de> {
+impl<'de, 'a, S: Source<'de>> SeqAccess<'de> for SequenceAccessor<'a, 'de, S> {
     type Error = Error;
 
     fn next_element_seed<T>(
@@ -230,18 +705,21 @@ impl<'de, 'a> SeqAccess<'de> for SequenceAccessor<'a, 'de> {
     }
 }
 
-struct DictionaryAccessor<'a, 'de: 'a> {
-    de: &'a mut Deserializer<'de>,
-    items: Vec<(Value, Value)>,
+struct DictionaryAccessor<'a, 'de, S: Source<'de>> {
+    de: &'a mut Deserializer<'de, S>,
+    items: Vec<(SourceValue<'de>, SourceValue<'de>)>,
 }
 
-impl<'a, 'de> DictionaryAccessor<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>, items: Vec<(Value, Value)>) -> Self {
+impl<'a, 'de, S: Source<'de>> DictionaryAccessor<'a, 'de, S> {
+    fn new(
+        de: &'a mut Deserializer<'de, S>,
+        items: Vec<(SourceValue<'de>, SourceValue<'de>)>,
+    ) -> Self {
         DictionaryAccessor { de, items }
     }
 }
 
-impl<'de, 'a> MapAccess<'de> for DictionaryAccessor<'a, 'de> {
+impl<'de, 'a, S: Source<'de>> MapAccess<'de> for DictionaryAccessor<'a, 'de, S> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> std::result::Result<Option<K::Value>, Self::Error>
@@ -250,8 +728,12 @@ impl<'de, 'a> MapAccess<'de> for DictionaryAccessor<'a, 'de> {
     {
         match self.items.last() {
             None => Ok(None),
-            Some(next) => {
-                self.de.pending.push(next.0.clone());
+            Some((key, _)) => {
+                let key = match key {
+                    SourceValue::Owned(v) => SourceValue::Owned(v.clone()),
+                    SourceValue::Borrowed(v) => SourceValue::Borrowed(v.clone()),
+                };
+                self.de.pending.push(key);
                 seed.deserialize(&mut *self.de).map(Some)
             }
         }
@@ -263,8 +745,8 @@ impl<'de, 'a> MapAccess<'de> for DictionaryAccessor<'a, 'de> {
     {
         match self.items.pop() {
             None => Err(Error::message("missing expected dictionary entry value")),
-            Some(next) => {
-                self.de.pending.push(next.1);
+            Some((_, value)) => {
+                self.de.pending.push(value);
                 seed.deserialize(&mut *self.de)
             }
         }
@@ -382,3 +864,154 @@ fn test_newtype_struct_from_record() {
         try_from_bytes::<Test>(br#"<4'Test3"foo42->"#.as_slice()),
     );
 }
+
+#[test]
+fn test_enum() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum E {
+        Unit,
+        Newtype(u32),
+        Tuple(u32, u32),
+        Struct { a: u32 },
+    }
+
+    assert_eq!(
+        Ok(E::Unit),
+        try_from_bytes::<E>(br#"<1'E4'Unit>"#.as_slice())
+    );
+    assert_eq!(
+        Ok(E::Newtype(1)),
+        try_from_bytes::<E>(br#"<1'E7'Newtype1+>"#.as_slice())
+    );
+    assert_eq!(
+        Ok(E::Tuple(1, 2)),
+        try_from_bytes::<E>(br#"<1'E5'Tuple1+2+>"#.as_slice())
+    );
+    assert_eq!(
+        Ok(E::Struct { a: 1 }),
+        try_from_bytes::<E>(br#"<1'E6'Struct{1'a1+}>"#.as_slice())
+    );
+}
+
+#[test]
+fn test_from_reader() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Test {
+        int: u32,
+        seq: Vec<String>,
+    }
+
+    let mut cursor = std::io::Cursor::new(br#"{3'int42+3'seq[3"foo3"bar]}"#.as_slice());
+    assert_eq!(
+        Test {
+            int: 42,
+            seq: vec!["foo".to_string(), "bar".to_string()]
+        },
+        from_reader::<Test, _>(&mut cursor).unwrap()
+    );
+}
+
+#[test]
+fn test_from_reader_reads_sequentially() {
+    let mut cursor = std::io::Cursor::new(br#"1+2+"#.as_slice());
+    assert_eq!(1u64, from_reader::<u64, _>(&mut cursor).unwrap());
+    assert_eq!(2u64, from_reader::<u64, _>(&mut cursor).unwrap());
+}
+
+#[test]
+fn test_borrows_str_without_copying() {
+    let input = br#"5"hello"#.as_slice();
+    let s: &str = try_from_bytes(input).unwrap();
+    assert_eq!(s, "hello");
+    // The borrowed slice points straight into `input`, not a fresh copy.
+    assert_eq!(s.as_ptr(), input[2..].as_ptr());
+}
+
+#[test]
+fn test_borrows_bytes_without_copying() {
+    let input = br#"5:hello"#.as_slice();
+    let bytes: &[u8] = try_from_bytes(input).unwrap();
+    assert_eq!(bytes, b"hello");
+    // The borrowed slice points straight into `input`, not a fresh copy.
+    assert_eq!(bytes.as_ptr(), input[2..].as_ptr());
+}
+
+#[test]
+fn test_borrows_struct_field_without_copying() {
+    #[derive(Deserialize, Debug)]
+    struct Test<'a> {
+        name: &'a str,
+        data: &'a [u8],
+    }
+
+    let input = br#"{4'name3"foo4'data3:bar}"#.as_slice();
+    let parsed: Test = try_from_bytes(input).unwrap();
+    assert_eq!(parsed.name, "foo");
+    assert_eq!(parsed.data, b"bar");
+    // Both fields should point somewhere inside `input`, not at a fresh
+    // allocation, regardless of exactly where each entry landed.
+    let bounds = input.as_ptr_range();
+    assert!(bounds.contains(&parsed.name.as_ptr()));
+    assert!(bounds.contains(&parsed.data.as_ptr()));
+}
+
+#[test]
+fn test_try_from_bytes_partial_returns_remainder() {
+    let (value, rest): (u64, &[u8]) = try_from_bytes_partial(br#"1+2+"#.as_slice()).unwrap();
+    assert_eq!(value, 1);
+    assert_eq!(rest, b"2+");
+}
+
+#[test]
+fn test_iter_from_bytes() {
+    let values: Vec<u64> = iter_from_bytes(br#"1+2+3+"#.as_slice())
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(values, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_iter_from_bytes_stops_at_error() {
+    let mut iter = iter_from_bytes::<u64>(br#"1+t"#.as_slice());
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_require_label_rejects_mismatched_struct_label() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Test {
+        int: u32,
+    }
+
+    let mut deserializer =
+        Deserializer::from_bytes(br#"<5'Other{3'int42+}>"#.as_slice()).require_label(true);
+    assert!(Test::deserialize(&mut deserializer).is_err());
+
+    let mut deserializer =
+        Deserializer::from_bytes(br#"<4'Test{3'int42+}>"#.as_slice()).require_label(true);
+    assert_eq!(
+        Test::deserialize(&mut deserializer).unwrap(),
+        Test { int: 42 }
+    );
+}
+
+#[test]
+fn test_require_label_rejects_mismatched_enum_label() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Test {
+        Variant(i32),
+    }
+
+    let mut deserializer =
+        Deserializer::from_bytes(br#"<5'Other7'Variant42+>"#.as_slice()).require_label(true);
+    assert!(Test::deserialize(&mut deserializer).is_err());
+
+    let mut deserializer =
+        Deserializer::from_bytes(br#"<4'Test7'Variant42+>"#.as_slice()).require_label(true);
+    assert_eq!(
+        Test::deserialize(&mut deserializer).unwrap(),
+        Test::Variant(42)
+    );
+}
@@ -5,12 +5,13 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, take},
     character::complete::digit1,
+    combinator::{consumed, map_res},
     error::context,
     multi::{length_count, many_till},
     sequence::{pair, preceded, terminated},
-    Finish, IResult, Parser,
+    Finish, IResult,
 };
-use num_bigint::{BigInt, Sign};
+use num_bigint::{BigInt, BigUint, Sign};
 
 /// Represent a parsed syrup value.
 ///
@@ -28,8 +29,17 @@ pub enum Value {
     Symbol(String),
     Dictionary(Vec<(Self, Self)>),
     Sequence(Vec<Self>),
-    Record { label: Box<Self>, fields: Vec<Self> },
+    Record {
+        label: Box<Self>,
+        fields: Vec<Self>,
+    },
     Set(Vec<Self>),
+    /// A domain-specific reference (an object capability, a sturdyref) that
+    /// isn't plain data. The format only ever sees this as an opaque,
+    /// length-prefixed byte payload (`len&bytes`, parallel to
+    /// [`Value::Binary`]'s `len:bytes`); a [`Domain`] is what gives those
+    /// bytes meaning.
+    Embedded(Vec<u8>),
 }
 
 impl Value {
@@ -82,6 +92,63 @@ impl Value {
         s.sort();
         Value::Set(s)
     }
+    /// Create a syrup embedded value by encoding `domain` with its [`Domain`]
+    /// codec.
+    pub fn embedded<D: Domain>(domain: &D) -> Value {
+        Value::Embedded(domain.encode())
+    }
+    /// Decode this value's embedded payload with `D`'s [`Domain`] codec.
+    /// Fails if this isn't an embedded value, or if `D` rejects the bytes.
+    pub fn as_embedded<D: Domain>(&self) -> Result<D> {
+        match self {
+            Value::Embedded(bytes) => D::decode(bytes),
+            _ => Err(Error::message("not an embedded value")),
+        }
+    }
+
+    /// This value's integer, if it is an integer.
+    pub fn as_bigint(&self) -> Option<&BigInt> {
+        match self {
+            Value::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+    /// This value as an `i64`, if it is an integer that fits in range.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_bigint().and_then(|i| i64::try_from(i).ok())
+    }
+    /// This value as a `u64`, if it is an integer that fits in range.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_bigint().and_then(|i| u64::try_from(i).ok())
+    }
+    /// This value as an `i128`, if it is an integer that fits in range.
+    pub fn as_i128(&self) -> Option<i128> {
+        self.as_bigint().and_then(|i| i128::try_from(i).ok())
+    }
+    /// This value as a `u128`, if it is an integer that fits in range.
+    pub fn as_u128(&self) -> Option<u128> {
+        self.as_bigint().and_then(|i| u128::try_from(i).ok())
+    }
+
+    /// Parse `input`, verifying that it's already in canonical syrup form
+    /// rather than silently accepting (and re-sorting) dictionaries and sets
+    /// whose entries arrive out of order or duplicated, the way [`TryFrom<&[u8]>`]
+    /// does. Dictionary keys and set elements must appear in strictly
+    /// ascending order by the same byte-order comparison [`Value::cmp`] uses
+    /// for sorting, with no duplicates, and integer magnitudes must have no
+    /// leading zero digits. Returns an [`Error::Parse`] pinpointing the first
+    /// violation.
+    ///
+    /// Useful for security-sensitive callers (signature verification,
+    /// content-addressing) that need to know a byte stream is already
+    /// canonical, rather than merely that re-encoding it would produce the
+    /// same bytes.
+    pub fn try_from_canonical(input: &[u8]) -> Result<Value> {
+        canonical_value(input)
+            .finish()
+            .map(|(_, v)| v)
+            .map_err(Error::from)
+    }
 
     /// Compare one syrup value to another, according to canonicalization rules
     /// for sorting.
@@ -102,9 +169,14 @@ impl Value {
                 } else {
                     "+"
                 };
-                format!("{}{}", big_int.magnitude().to_str_radix(10), suffix)
-                    .as_bytes()
-                    .to_vec()
+                // Fast path: format the common small-integer case (ages,
+                // counts, ...) through a native `u128` instead of BigUint's
+                // general-purpose radix conversion.
+                let magnitude = match u128::try_from(big_int.magnitude()) {
+                    Ok(small) => small.to_string(),
+                    Err(_) => big_int.magnitude().to_str_radix(10),
+                };
+                format!("{}{}", magnitude, suffix).as_bytes().to_vec()
             }
             Value::Binary(b) => [format!("{}:", b.len()).as_bytes(), b].concat(),
             Value::String(s) => {
@@ -155,15 +227,80 @@ impl Value {
                 [b'$'].as_slice(),
             ]
             .concat(),
+            Value::Embedded(b) => [format!("{}&", b.len()).as_bytes(), b].concat(),
         }
     }
 }
 
+/// How embedded/capability references are framed on the wire and
+/// reconstituted in memory. An application plugs in its own `Domain` to
+/// interpret a [`Value::Embedded`] payload as something richer than opaque
+/// bytes (an object capability, a sturdyref) and back.
+pub trait Domain {
+    /// Encode this domain value as its on-wire embedded payload.
+    fn encode(&self) -> Vec<u8>;
+    /// Decode a domain value from its on-wire embedded payload.
+    fn decode(bytes: &[u8]) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+/// The default domain: no embedded values are recognized, so decoding one
+/// always fails. Since `NoEmbedded` has no values, encoding one is
+/// unreachable.
+pub enum NoEmbedded {}
+
+impl Domain for NoEmbedded {
+    fn encode(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    fn decode(_bytes: &[u8]) -> Result<Self> {
+        Err(Error::message("embedded values are not supported"))
+    }
+}
+
 /// Error during syrup format processing.
 #[derive(Debug, PartialEq)]
 pub enum Error {
     Message(String),
     Parse(String),
+    /// Input ended where a value was expected: nothing was left to decode,
+    /// or a [`Reader`](crate::reader::Reader) stream ended before a
+    /// sequence, dictionary, record or set was closed. A length-prefixed
+    /// term cut short by the end of the input is reported by the parser that
+    /// read it instead, as [`Error::Syntax`], [`Error::Parse`] or
+    /// [`Error::Io`].
+    Eof,
+    /// The underlying `io::Write`/`io::Read` sink failed.
+    Io(String),
+    /// A syrup term was malformed at `offset` bytes into the input, for the
+    /// given `reason`. Unlike [`Error::Parse`], this is produced by the
+    /// zero-copy `ValueRef` parser, which reports a byte offset rather than a
+    /// snippet of the input. The offset points at the outermost term that
+    /// failed to parse, or just past its leading digits; a failure nested
+    /// inside a sequence, dictionary, record or set is reported at the start
+    /// of that outermost value.
+    Syntax {
+        offset: usize,
+        reason: String,
+    },
+    /// Produced for a nom `Incomplete` result from the zero-copy `ValueRef`
+    /// parser. That parser runs in complete mode, so input that ends inside
+    /// a term's declared length, or before a composite value is closed, is
+    /// reported as [`Error::Syntax`] instead.
+    IncompleteInput,
+    /// Extra bytes were left over after the expected value(s) were decoded,
+    /// starting at `offset`.
+    TrailingGarbage {
+        offset: usize,
+    },
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
 }
 
 impl Error {
@@ -179,14 +316,39 @@ impl Display for Error {
         match self {
             Error::Message(msg) => f.write_str(msg),
             Error::Parse(msg) => f.write_str(msg),
+            Error::Eof => f.write_str("unexpected end of input"),
+            Error::Io(msg) => f.write_str(msg),
+            Error::Syntax { offset, reason } => {
+                write!(f, "syntax error at byte {}: {}", offset, reason)
+            }
+            Error::IncompleteInput => f.write_str("unexpected end of input"),
+            Error::TrailingGarbage { offset } => {
+                write!(f, "trailing garbage at byte {}", offset)
+            }
         }
     }
 }
 
+/// Convert a nom parse failure from the zero-copy [`crate::value_ref`] parser
+/// into an [`Error::Syntax`]/[`Error::IncompleteInput`], pointing at `offset`
+/// bytes into `original` rather than just describing the failure in prose.
+/// `original` and the error's leftover slice must come from the same buffer,
+/// as is always true for nom combinators, which only ever hand back
+/// subslices of what they were given.
+pub(crate) fn offset_error(original: &[u8], err: nom::Err<nom::error::Error<&[u8]>>) -> Error {
+    match err {
+        nom::Err::Incomplete(_) => Error::IncompleteInput,
+        nom::Err::Error(e) | nom::Err::Failure(e) => Error::Syntax {
+            offset: original.len() - e.input.len(),
+            reason: e.code.description().to_string(),
+        },
+    }
+}
+
 impl From<nom::Err<nom::error::Error<&[u8]>>> for Error {
     fn from(value: nom::Err<nom::error::Error<&[u8]>>) -> Self {
         match value {
-            nom::Err::Incomplete(_) => Error::Message("incomplete input".to_string()),
+            nom::Err::Incomplete(_) => Error::Eof,
             nom::Err::Error(e) => e.into(),
             nom::Err::Failure(e) => e.into(),
         }
@@ -241,6 +403,7 @@ pub(crate) fn value(input: &[u8]) -> IResult<&[u8], Value> {
             dictionary_value,
             sequence_value,
             record_value,
+            embedded_value,
             set_value,
         )),
     )(input)
@@ -287,77 +450,92 @@ fn integer_value(input: &[u8]) -> IResult<&[u8], Value> {
         };
         (
             next_input,
-            Value::Integer(
-                BigInt::from_radix_be(
-                    sign,
-                    num_str
-                        .iter()
-                        .map(|d| d - 0x30)
-                        .collect::<Vec<u8>>()
-                        .as_slice(),
-                    10,
-                )
-                .unwrap(),
-            ),
+            Value::Integer(parse_decimal_bigint(sign, num_str)),
         )
     })
 }
 
+/// Parse a decimal magnitude into a [`BigInt`]. Takes a fast path through
+/// `u128` when the magnitude fits (the common case for ages, counts, and
+/// other small integers), avoiding the per-digit byte vector
+/// [`BigInt::from_radix_be`] otherwise needs; falls back to that for larger
+/// magnitudes.
+fn parse_decimal_bigint(sign: Sign, digits: &[u8]) -> BigInt {
+    if digits.len() <= 38 {
+        // u128::MAX has 39 decimal digits, so anything up to 38 digits fits.
+        if let Ok(small) = std::str::from_utf8(digits).unwrap().parse::<u128>() {
+            return BigInt::from_biguint(sign, BigUint::from(small));
+        }
+    }
+    BigInt::from_radix_be(
+        sign,
+        digits
+            .iter()
+            .map(|d| d - 0x30)
+            .collect::<Vec<u8>>()
+            .as_slice(),
+        10,
+    )
+    .unwrap()
+}
+
+/// Parse a Syrup length prefix (`n:` / `n"` / `n'` / `n&`) terminated by
+/// `tag_str`, failing instead of panicking if the digits overflow `u32`.
+fn counted_length<'a>(tag_str: &'static str) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], u32> {
+    move |input| {
+        terminated(
+            map_res(digit1, |d: &[u8]| {
+                std::str::from_utf8(d)
+                    .ok()
+                    .and_then(|s| u32::from_str(s).ok())
+                    .ok_or(())
+            }),
+            tag(tag_str),
+        )(input)
+    }
+}
+
 fn binary_value(input: &[u8]) -> IResult<&[u8], Value> {
-    context(
-        "binary",
-        length_count(
-            terminated(digit1, tag(":"))
-                .map(|res| u32::from_str(String::from_utf8_lossy(res).as_ref()).unwrap()),
-            take(1u8),
-        ),
-    )(input)
-    .map(|(next_input, res)| {
-        (
-            next_input,
-            Value::Binary(res.iter().map(|b| b[0]).collect()),
-        )
-    })
+    context("binary", length_count(counted_length(":"), take(1u8)))(input).map(
+        |(next_input, res)| {
+            (
+                next_input,
+                Value::Binary(res.iter().map(|b| b[0]).collect()),
+            )
+        },
+    )
 }
 
 fn string_value(input: &[u8]) -> IResult<&[u8], Value> {
-    context(
-        "string",
-        length_count(
-            terminated(digit1, tag("\""))
-                .map(|res| u32::from_str(String::from_utf8_lossy(res).as_ref()).unwrap()),
-            take(1u8),
-        ),
-    )(input)
-    .map(|(next_input, res)| {
-        (
-            next_input,
-            Value::String(
-                String::from_utf8_lossy(res.iter().map(|b| b[0]).collect::<Vec<u8>>().as_slice())
+    context("string", length_count(counted_length("\""), take(1u8)))(input).map(
+        |(next_input, res)| {
+            (
+                next_input,
+                Value::String(
+                    String::from_utf8_lossy(
+                        res.iter().map(|b| b[0]).collect::<Vec<u8>>().as_slice(),
+                    )
                     .into_owned(),
-            ),
-        )
-    })
+                ),
+            )
+        },
+    )
 }
 
 fn symbol_value(input: &[u8]) -> IResult<&[u8], Value> {
-    context(
-        "symbol",
-        length_count(
-            terminated(digit1, tag("\'"))
-                .map(|res| u32::from_str(String::from_utf8_lossy(res).as_ref()).unwrap()),
-            take(1u8),
-        ),
-    )(input)
-    .map(|(next_input, res)| {
-        (
-            next_input,
-            Value::Symbol(
-                String::from_utf8_lossy(res.iter().map(|b| b[0]).collect::<Vec<u8>>().as_slice())
+    context("symbol", length_count(counted_length("\'"), take(1u8)))(input).map(
+        |(next_input, res)| {
+            (
+                next_input,
+                Value::Symbol(
+                    String::from_utf8_lossy(
+                        res.iter().map(|b| b[0]).collect::<Vec<u8>>().as_slice(),
+                    )
                     .into_owned(),
-            ),
-        )
-    })
+                ),
+            )
+        },
+    )
 }
 
 fn sequence_value(input: &[u8]) -> IResult<&[u8], Value> {
@@ -401,6 +579,132 @@ fn set_value(input: &[u8]) -> IResult<&[u8], Value> {
     )
 }
 
+fn embedded_value(input: &[u8]) -> IResult<&[u8], Value> {
+    context("embedded", length_count(counted_length("&"), take(1u8)))(input).map(
+        |(next_input, res)| {
+            (
+                next_input,
+                Value::Embedded(res.iter().map(|b| b[0]).collect()),
+            )
+        },
+    )
+}
+
+fn canonical_value(input: &[u8]) -> IResult<&[u8], Value> {
+    context(
+        "value",
+        alt((
+            boolean_value,
+            float_value,
+            double_value,
+            canonical_integer_value,
+            binary_value,
+            string_value,
+            symbol_value,
+            canonical_dictionary_value,
+            canonical_sequence_value,
+            canonical_record_value,
+            embedded_value,
+            canonical_set_value,
+        )),
+    )(input)
+}
+
+/// Like [`integer_value`], but rejects a magnitude with a leading zero digit
+/// (e.g. `"007+"`), which [`integer_value`] would otherwise happily
+/// parse as `7` and so isn't caught by a round-trip comparison alone.
+fn canonical_integer_value(input: &[u8]) -> IResult<&[u8], Value> {
+    let (next_input, (digits, value)) = consumed(integer_value)(input)?;
+    let magnitude = &digits[..digits.len() - 1];
+    if magnitude.len() > 1 && magnitude[0] == b'0' {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            digits,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    Ok((next_input, value))
+}
+
+/// Like [`sequence_value`], but recurses through [`canonical_value`] so a
+/// dictionary or set nested inside is validated too.
+fn canonical_sequence_value(input: &[u8]) -> IResult<&[u8], Value> {
+    context(
+        "sequence",
+        preceded(tag("["), many_till(canonical_value, tag("]"))),
+    )(input)
+    .map(|(next_input, res)| (next_input, Value::Sequence(res.0)))
+}
+
+/// Like [`dictionary_value`], but verifies keys arrive in strictly ascending
+/// order with no duplicates instead of sorting them.
+fn canonical_dictionary_value(input: &[u8]) -> IResult<&[u8], Value> {
+    context(
+        "dictionary",
+        preceded(
+            tag("{"),
+            many_till(pair(consumed(canonical_value), canonical_value), tag("}")),
+        ),
+    )(input)
+    .and_then(|(next_input, (entries, _))| {
+        for window in entries.windows(2) {
+            let ((prev_key, _), _) = &window[0];
+            let ((curr_key, _), _) = &window[1];
+            if curr_key <= prev_key {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    *curr_key,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+        let entries = entries.into_iter().map(|((_, k), v)| (k, v)).collect();
+        Ok((next_input, Value::Dictionary(entries)))
+    })
+}
+
+/// Like [`record_value`], but recurses through [`canonical_value`] so a
+/// dictionary or set nested inside is validated too.
+fn canonical_record_value(input: &[u8]) -> IResult<&[u8], Value> {
+    context(
+        "sequence",
+        preceded(
+            tag("<"),
+            pair(canonical_value, many_till(canonical_value, tag(">"))),
+        ),
+    )(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            Value::Record {
+                label: Box::new(res.0),
+                fields: res.1 .0,
+            },
+        )
+    })
+}
+
+/// Like [`set_value`], but verifies elements arrive in strictly ascending
+/// order with no duplicates instead of sorting them.
+fn canonical_set_value(input: &[u8]) -> IResult<&[u8], Value> {
+    context(
+        "sequence",
+        preceded(tag("#"), many_till(consumed(canonical_value), tag("$"))),
+    )(input)
+    .and_then(|(next_input, (elements, _))| {
+        for window in elements.windows(2) {
+            let (prev, _) = &window[0];
+            let (curr, _) = &window[1];
+            if curr <= prev {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    *curr,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+        let elements = elements.into_iter().map(|(_, v)| v).collect();
+        Ok((next_input, Value::Set(elements)))
+    })
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         return self.cmp(other).is_eq();
@@ -500,6 +804,10 @@ mod tests {
             Value::from_str("#3\"foo3\"bar$"),
             Ok(Value::set(vec![Value::string("bar"), Value::string("foo")]))
         );
+        assert_eq!(
+            Value::from_str("5&hello"),
+            Ok(Value::Embedded(b"hello".to_vec()))
+        );
     }
 
     #[test]
@@ -516,6 +824,7 @@ mod tests {
             "{3\"foo3\"bar3\"goo4\"muck}",
             "<6:person5:Alice30+t>",
             "#3\"bar3\"foo$",
+            "5&hello",
         ] {
             assert_eq!(
                 Value::from_str(s).unwrap().to_vec(),
@@ -526,6 +835,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn embedded_domain_round_trip() {
+        struct Counter(u32);
+
+        impl Domain for Counter {
+            fn encode(&self) -> Vec<u8> {
+                self.0.to_be_bytes().to_vec()
+            }
+
+            fn decode(bytes: &[u8]) -> Result<Self> {
+                let bytes: [u8; 4] = bytes.try_into().map_err(Error::message)?;
+                Ok(Counter(u32::from_be_bytes(bytes)))
+            }
+        }
+
+        let value = Value::embedded(&Counter(7));
+        assert_eq!(value.to_vec(), b"4&\x00\x00\x00\x07".to_vec());
+        assert_eq!(value.as_embedded::<Counter>().unwrap().0, 7);
+        assert!(Value::string("nope").as_embedded::<Counter>().is_err());
+        assert!(value.as_embedded::<NoEmbedded>().is_err());
+    }
+
+    #[test]
+    fn try_from_canonical_accepts_canonical_form() {
+        assert_eq!(
+            Value::try_from_canonical(b"{3\"bar3\"baz3\"foo3\"qux}"),
+            Ok(Value::Dictionary(vec![
+                (Value::string("bar"), Value::string("baz")),
+                (Value::string("foo"), Value::string("qux")),
+            ]))
+        );
+        assert_eq!(
+            Value::try_from_canonical(b"#3\"bar3\"foo$"),
+            Ok(Value::set(vec![Value::string("bar"), Value::string("foo")]))
+        );
+        assert_eq!(Value::try_from_canonical(b"10+"), Ok(Value::integer(10)));
+        assert_eq!(Value::try_from_canonical(b"0+"), Ok(Value::integer(0)));
+    }
+
+    #[test]
+    fn try_from_canonical_rejects_out_of_order_dictionary_keys() {
+        assert!(Value::try_from_canonical(b"{3\"foo3\"qux3\"bar3\"baz}").is_err());
+    }
+
+    #[test]
+    fn try_from_canonical_rejects_duplicate_dictionary_keys() {
+        assert!(Value::try_from_canonical(b"{3\"bar3\"baz3\"bar3\"qux}").is_err());
+    }
+
+    #[test]
+    fn try_from_canonical_rejects_out_of_order_set_elements() {
+        assert!(Value::try_from_canonical(b"#3\"foo3\"bar$").is_err());
+    }
+
+    #[test]
+    fn try_from_canonical_rejects_leading_zero_integers() {
+        assert!(Value::try_from_canonical(b"007+").is_err());
+    }
+
+    #[test]
+    fn try_from_canonical_validates_nested_structures() {
+        assert!(Value::try_from_canonical(b"[{3\"foo3\"qux3\"bar3\"baz}]").is_err());
+        assert!(Value::try_from_canonical(b"<3\"foo{3\"bar3\"baz3\"foo3\"qux}>").is_ok());
+    }
+
+    #[test]
+    fn integer_accessors() {
+        assert_eq!(Value::integer(42).as_i64(), Some(42));
+        assert_eq!(Value::integer(-42).as_i64(), Some(-42));
+        assert_eq!(Value::integer(42).as_u64(), Some(42));
+        assert_eq!(Value::integer(-42).as_u64(), None);
+        assert_eq!(Value::integer(u128::MAX).as_u128(), Some(u128::MAX));
+        assert_eq!(Value::integer(i128::MIN).as_i128(), Some(i128::MIN));
+        assert_eq!(Value::integer(u128::MAX).as_u64(), None);
+        assert_eq!(
+            Value::integer(u128::MAX).as_bigint(),
+            Some(&BigInt::from(u128::MAX))
+        );
+        assert_eq!(Value::string("nope").as_i64(), None);
+        assert_eq!(Value::string("nope").as_bigint(), None);
+    }
+
+    #[test]
+    fn rejects_oversized_length_prefix() {
+        // A length prefix that overflows u32 must fail to parse rather than
+        // panicking, for every length-prefixed value kind, not just binary.
+        assert!(Value::try_from(b"99999999999999999999:hello".as_slice()).is_err());
+        assert!(Value::try_from(b"99999999999999999999\"hello".as_slice()).is_err());
+        assert!(Value::try_from(b"99999999999999999999'hello".as_slice()).is_err());
+        assert!(Value::try_from(b"99999999999999999999&hello".as_slice()).is_err());
+    }
+
+    #[test]
+    fn round_trips_integer_beyond_u128() {
+        let huge = BigInt::from(u128::MAX) * BigInt::from(1000);
+        let value = Value::Integer(huge.clone());
+        assert_eq!(
+            Value::from_str(&String::from_utf8(value.to_vec()).unwrap()).unwrap(),
+            value
+        );
+        assert_eq!(value.as_u128(), None);
+        assert_eq!(value.as_bigint(), Some(&huge));
+    }
+
     #[test]
     fn parse_zoo() {
         let zoo_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
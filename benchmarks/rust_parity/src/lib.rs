@@ -1,7 +1,21 @@
 mod de;
 mod format;
+pub mod path;
+mod reader;
+pub mod schema;
 mod ser;
+mod value_ref;
+mod writer;
 
-pub use de::{from_value, try_from_bytes, Deserializer};
-pub use format::{Error, Result, Value};
-pub use ser::{to_vec, Serializer};
+pub use de::{
+    from_reader, from_slice, from_value, iter_from_bytes, try_from_bytes, try_from_bytes_partial,
+    Deserializer, FromBytesIter, IoRead, Source, SourceValue,
+};
+pub use format::{Domain, Error, NoEmbedded, Result, Value};
+pub use reader::{Event, Reader};
+pub use ser::{
+    to_value, to_vec, to_vec_canonical, to_writer, to_writer_canonical, Embedded, Serializer, Set,
+    Symbol,
+};
+pub use value_ref::ValueRef;
+pub use writer::Writer;
@@ -0,0 +1,242 @@
+//! Query a parsed [`Value`] tree with a compiled selector, instead of
+//! hand-walking its `Dictionary`/`Sequence`/`Record`/`Set` arms.
+
+use crate::format::Value;
+
+/// One step of a [`Selector`], mapping a set of matched nodes to a new set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// All immediate children: the elements of a sequence or set, the
+    /// values (not keys) of a dictionary, or the label followed by the
+    /// fields of a record.
+    Values,
+    /// All transitive descendants, however deeply nested.
+    Descendants,
+    /// The label of a record.
+    Label,
+    /// The nth record field, or the nth sequence element.
+    Field(usize),
+    /// The dictionary value for the given canonical key.
+    DictKey(Value),
+}
+
+/// How a [`Predicate::Compare`] relates a node to its `rhs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A filter applied to a selector's matched node set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Eq(Value),
+    Compare {
+        op: CompareOp,
+        rhs: Value,
+    },
+    /// Always matches; useful as a base case under `Not`/`And`/`Or`.
+    Exists,
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    fn eval(&self, node: &Value) -> bool {
+        match self {
+            Predicate::Eq(v) => node == v,
+            Predicate::Compare { op, rhs } => {
+                let ord = node.cmp(rhs);
+                match op {
+                    CompareOp::Lt => ord.is_lt(),
+                    CompareOp::Le => ord.is_le(),
+                    CompareOp::Gt => ord.is_gt(),
+                    CompareOp::Ge => ord.is_ge(),
+                }
+            }
+            Predicate::Exists => true,
+            Predicate::And(a, b) => a.eval(node) && b.eval(node),
+            Predicate::Or(a, b) => a.eval(node) || b.eval(node),
+            Predicate::Not(p) => !p.eval(node),
+        }
+    }
+}
+
+/// An ordered list of [`Step`]s, optionally followed by a [`Predicate`]
+/// filter, compiled once and run against any number of [`Value`] trees.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selector {
+    steps: Vec<Step>,
+    predicate: Option<Predicate>,
+}
+
+impl Selector {
+    pub fn new() -> Self {
+        Selector::default()
+    }
+
+    /// Append a step to the selector.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Filter the selector's final matched node set with `predicate`.
+    pub fn filter(mut self, predicate: Predicate) -> Self {
+        self.predicate = Some(predicate);
+        self
+    }
+
+    /// Run the selector against `root`, returning every matching node.
+    pub fn exec<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut matched = vec![root];
+        for step in &self.steps {
+            matched = matched
+                .into_iter()
+                .flat_map(|node| apply(step, node))
+                .collect();
+        }
+        match &self.predicate {
+            Some(predicate) => matched
+                .into_iter()
+                .filter(|node| predicate.eval(node))
+                .collect(),
+            None => matched,
+        }
+    }
+}
+
+fn apply<'a>(step: &Step, node: &'a Value) -> Vec<&'a Value> {
+    match step {
+        Step::Values => children(node),
+        Step::Descendants => {
+            let mut out = vec![];
+            for child in children(node) {
+                out.push(child);
+                out.extend(apply(&Step::Descendants, child));
+            }
+            out
+        }
+        Step::Label => match node {
+            Value::Record { label, .. } => vec![label.as_ref()],
+            _ => vec![],
+        },
+        Step::Field(i) => match node {
+            Value::Record { fields, .. } => fields.get(*i).into_iter().collect(),
+            Value::Sequence(items) => items.get(*i).into_iter().collect(),
+            _ => vec![],
+        },
+        Step::DictKey(key) => match node {
+            Value::Dictionary(entries) => entries
+                .iter()
+                .filter(|(k, _)| k == key)
+                .map(|(_, v)| v)
+                .collect(),
+            _ => vec![],
+        },
+    }
+}
+
+/// The immediate children of `node`, in the sense used by `Step::Values`.
+fn children(node: &Value) -> Vec<&Value> {
+    match node {
+        Value::Sequence(items) | Value::Set(items) => items.iter().collect(),
+        Value::Dictionary(entries) => entries.iter().map(|(_, v)| v).collect(),
+        Value::Record { label, fields } => {
+            let mut out = vec![label.as_ref()];
+            out.extend(fields.iter());
+            out
+        }
+        _ => vec![],
+    }
+}
+
+#[test]
+fn test_values_and_field_steps() {
+    let doc = Value::sequence(vec![
+        Value::integer(1),
+        Value::integer(2),
+        Value::integer(3),
+    ]);
+
+    assert_eq!(
+        Selector::new().step(Step::Values).exec(&doc),
+        vec![&Value::integer(1), &Value::integer(2), &Value::integer(3)]
+    );
+    assert_eq!(
+        Selector::new().step(Step::Field(1)).exec(&doc),
+        vec![&Value::integer(2)]
+    );
+}
+
+#[test]
+fn test_dict_key_and_label_steps() {
+    let record = Value::record(
+        Value::symbol("animal"),
+        vec![Value::dictionary(vec![
+            (Value::symbol("name"), Value::string("Tabatha")),
+            (Value::symbol("age"), Value::integer(12)),
+        ])],
+    );
+
+    assert_eq!(
+        Selector::new().step(Step::Label).exec(&record),
+        vec![&Value::symbol("animal")]
+    );
+    assert_eq!(
+        Selector::new()
+            .step(Step::Field(0))
+            .step(Step::DictKey(Value::symbol("name")))
+            .exec(&record),
+        vec![&Value::string("Tabatha")]
+    );
+}
+
+#[test]
+fn test_descendants_step() {
+    let doc = Value::sequence(vec![
+        Value::sequence(vec![Value::integer(1), Value::integer(2)]),
+        Value::integer(3),
+    ]);
+
+    let descendants = Selector::new().step(Step::Descendants).exec(&doc);
+    assert_eq!(
+        descendants,
+        vec![
+            &Value::sequence(vec![Value::integer(1), Value::integer(2)]),
+            &Value::integer(1),
+            &Value::integer(2),
+            &Value::integer(3),
+        ]
+    );
+}
+
+#[test]
+fn test_predicate_filter() {
+    let doc = Value::sequence(vec![
+        Value::integer(1),
+        Value::integer(2),
+        Value::integer(3),
+    ]);
+
+    assert_eq!(
+        Selector::new()
+            .step(Step::Values)
+            .filter(Predicate::Compare {
+                op: CompareOp::Gt,
+                rhs: Value::integer(1),
+            })
+            .exec(&doc),
+        vec![&Value::integer(2), &Value::integer(3)]
+    );
+    assert_eq!(
+        Selector::new()
+            .step(Step::Values)
+            .filter(Predicate::Not(Box::new(Predicate::Eq(Value::integer(2)))))
+            .exec(&doc),
+        vec![&Value::integer(1), &Value::integer(3)]
+    );
+}
@@ -0,0 +1,242 @@
+use std::io::Read as IoRead;
+use std::str::FromStr;
+
+use num_bigint::{BigInt, Sign};
+
+use crate::format::{Error, Result, Value};
+
+/// Upper bound on how much we'll allocate up front for a single length-
+/// prefixed payload, regardless of what length the input claims. Bytes
+/// beyond this are still read in further chunks of the same size, so
+/// arbitrarily large (but actually-present) payloads still work; this
+/// just keeps a bogus length prefix from triggering one huge allocation
+/// before we've confirmed the input actually has that many bytes.
+const MAX_COUNTED_CHUNK: usize = 64 * 1024;
+
+/// One token of a syrup value as it's pulled off a [`Reader`], without
+/// waiting for the whole document (or even the whole containing sequence,
+/// dictionary, record or set) to arrive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    BeginSequence,
+    BeginDictionary,
+    BeginRecord,
+    BeginSet,
+    /// A complete leaf value: booleans, numbers, binary data, strings and
+    /// symbols are never split across events.
+    Atom(Value),
+    /// Closes whichever `Begin*` event is currently open.
+    End,
+}
+
+/// Decodes a syrup value from a `std::io::Read` source one token at a time,
+/// rather than running the nom parser over a fully-buffered slice the way
+/// `Value::try_from(&[u8])` does. This allows reading values larger than
+/// memory, or straight off a socket.
+pub struct Reader<R> {
+    input: R,
+}
+
+impl<R: IoRead> Reader<R> {
+    pub fn new(input: R) -> Self {
+        Reader { input }
+    }
+
+    /// Recover the underlying source.
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+
+    /// Pull the next event off the stream, or `Ok(None)` at end of input.
+    pub fn next_event(&mut self) -> Result<Option<Event>> {
+        let mut byte = [0u8; 1];
+        if self.input.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+        match byte[0] {
+            b't' => Ok(Some(Event::Atom(Value::Boolean(true)))),
+            b'f' => Ok(Some(Event::Atom(Value::Boolean(false)))),
+            b'F' => {
+                let mut buf = [0u8; 4];
+                self.input.read_exact(&mut buf)?;
+                Ok(Some(Event::Atom(Value::Float(f32::from_be_bytes(buf)))))
+            }
+            b'D' => {
+                let mut buf = [0u8; 8];
+                self.input.read_exact(&mut buf)?;
+                Ok(Some(Event::Atom(Value::Double(f64::from_be_bytes(buf)))))
+            }
+            b'[' => Ok(Some(Event::BeginSequence)),
+            b'{' => Ok(Some(Event::BeginDictionary)),
+            b'<' => Ok(Some(Event::BeginRecord)),
+            b'#' => Ok(Some(Event::BeginSet)),
+            b']' | b'}' | b'>' | b'$' => Ok(Some(Event::End)),
+            d if d.is_ascii_digit() => {
+                let mut digits = vec![d];
+                let terminator = loop {
+                    self.input.read_exact(&mut byte)?;
+                    if byte[0].is_ascii_digit() {
+                        digits.push(byte[0]);
+                    } else {
+                        break byte[0];
+                    }
+                };
+                match terminator {
+                    b'+' | b'-' => {
+                        let sign = if terminator == b'-' {
+                            Sign::Minus
+                        } else {
+                            Sign::Plus
+                        };
+                        let magnitude = digits.iter().map(|d| d - 0x30).collect::<Vec<u8>>();
+                        Ok(Some(Event::Atom(Value::Integer(
+                            BigInt::from_radix_be(sign, &magnitude, 10).unwrap(),
+                        ))))
+                    }
+                    b':' | b'"' | b'\'' | b'&' => {
+                        let len =
+                            usize::from_str(std::str::from_utf8(&digits).map_err(Error::message)?)
+                                .map_err(Error::message)?;
+                        let buf = self.read_counted(len)?;
+                        Ok(Some(Event::Atom(match terminator {
+                            b':' => Value::Binary(buf),
+                            b'"' => Value::String(String::from_utf8_lossy(&buf).into_owned()),
+                            b'\'' => Value::Symbol(String::from_utf8_lossy(&buf).into_owned()),
+                            b'&' => Value::Embedded(buf),
+                            _ => unreachable!("matched above"),
+                        })))
+                    }
+                    other => Err(Error::message(format!(
+                        "unexpected byte {:#x} after length prefix",
+                        other
+                    ))),
+                }
+            }
+            other => Err(Error::message(format!("unexpected byte {:#x}", other))),
+        }
+    }
+
+    /// Read exactly `len` bytes off the input, growing the buffer in
+    /// [`MAX_COUNTED_CHUNK`]-sized steps instead of allocating `len` bytes
+    /// up front, so a bogus length prefix (e.g. a multi-terabyte claim from
+    /// untrusted input) can't force a huge allocation before we've actually
+    /// confirmed the input has that many bytes.
+    fn read_counted(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(len.min(MAX_COUNTED_CHUNK));
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(MAX_COUNTED_CHUNK);
+            let start = buf.len();
+            buf.resize(start + chunk, 0);
+            self.input.read_exact(&mut buf[start..])?;
+            remaining -= chunk;
+        }
+        Ok(buf)
+    }
+
+    /// Pull events until a complete [`Value`] has been assembled.
+    pub fn read_value(&mut self) -> Result<Value> {
+        let event = self.next_event()?.ok_or(Error::Eof)?;
+        self.assemble(event)
+    }
+
+    /// Like [`Reader::read_value`], but returns `Ok(None)` at end of input
+    /// instead of [`Error::Eof`], so a caller can tell "nothing left to
+    /// read" apart from a genuine parse failure.
+    pub fn try_read_value(&mut self) -> Result<Option<Value>> {
+        match self.next_event()? {
+            None => Ok(None),
+            Some(event) => self.assemble(event).map(Some),
+        }
+    }
+
+    fn assemble(&mut self, event: Event) -> Result<Value> {
+        match event {
+            Event::Atom(v) => Ok(v),
+            Event::End => Err(Error::message("unexpected end of value")),
+            Event::BeginSequence => Ok(Value::Sequence(self.read_items()?)),
+            Event::BeginDictionary => {
+                let items = self.read_items()?;
+                if items.len() % 2 != 0 {
+                    return Err(Error::message("dictionary has an odd number of entries"));
+                }
+                let mut entries = vec![];
+                let mut items = items.into_iter();
+                while let (Some(k), Some(v)) = (items.next(), items.next()) {
+                    entries.push((k, v));
+                }
+                Ok(Value::dictionary(entries))
+            }
+            Event::BeginRecord => {
+                let mut items = self.read_items()?;
+                if items.is_empty() {
+                    return Err(Error::message("record is missing a label"));
+                }
+                let label = items.remove(0);
+                Ok(Value::Record {
+                    label: Box::new(label),
+                    fields: items,
+                })
+            }
+            Event::BeginSet => Ok(Value::set(self.read_items()?)),
+        }
+    }
+
+    fn read_items(&mut self) -> Result<Vec<Value>> {
+        let mut items = vec![];
+        loop {
+            match self.next_event()?.ok_or(Error::Eof)? {
+                Event::End => break,
+                event => items.push(self.assemble(event)?),
+            }
+        }
+        Ok(items)
+    }
+}
+
+#[test]
+fn test_read_value_matches_parser() {
+    for s in [
+        "t",
+        "f",
+        "10+",
+        "10-",
+        "5:hello",
+        "3\"foo",
+        "4'none",
+        "[1+2+3+]",
+        "{3\"foo3\"bar3\"goo4\"muck}",
+        "<6:person5:Alice30+t>",
+        "#3\"bar3\"foo$",
+        "5&hello",
+    ] {
+        let expected = Value::from_str(s).unwrap();
+        let actual = Reader::new(s.as_bytes()).read_value().unwrap();
+        assert_eq!(actual, expected, "read_value: {}", s);
+    }
+}
+
+#[test]
+fn test_next_event_rejects_truncated_payload_without_huge_allocation() {
+    // A declared length far larger than the bytes actually available must
+    // error out (once the input is exhausted) rather than succeeding or
+    // attempting to allocate the whole claimed length up front.
+    let mut reader = Reader::new(b"999999999999:hello".as_slice());
+    assert!(reader.next_event().is_err());
+}
+
+#[test]
+fn test_next_event_sequence() {
+    let mut reader = Reader::new(br#"[1+2+]"#.as_slice());
+    assert_eq!(reader.next_event().unwrap(), Some(Event::BeginSequence));
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Event::Atom(Value::integer(1)))
+    );
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Event::Atom(Value::integer(2)))
+    );
+    assert_eq!(reader.next_event().unwrap(), Some(Event::End));
+    assert_eq!(reader.next_event().unwrap(), None);
+}
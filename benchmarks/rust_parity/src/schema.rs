@@ -0,0 +1,384 @@
+//! Validate a parsed [`Value`] against a declared [`Schema`], producing a
+//! structured [`SchemaError`] pinpointing the failing node rather than
+//! hand-rolling `match`es over the document's shape.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use crate::format::Value;
+
+/// The kind of atomic leaf value a [`Schema::Atom`] expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomKind {
+    Boolean,
+    Float,
+    Double,
+    Integer,
+    Binary,
+    String,
+    Symbol,
+}
+
+impl AtomKind {
+    fn matches(self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (AtomKind::Boolean, Value::Boolean(_))
+                | (AtomKind::Float, Value::Float(_))
+                | (AtomKind::Double, Value::Double(_))
+                | (AtomKind::Integer, Value::Integer(_))
+                | (AtomKind::Binary, Value::Binary(_))
+                | (AtomKind::String, Value::String(_))
+                | (AtomKind::Symbol, Value::Symbol(_))
+        )
+    }
+}
+
+/// A description of the expected shape of a [`Value`], combinable to match
+/// nested documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// A leaf value of the given kind.
+    Atom(AtomKind),
+    /// A record with an exact label and a fixed, ordered list of fields.
+    Record { label: Value, fields: Vec<Schema> },
+    /// A sequence whose elements all match the inner schema.
+    Sequenceof(Box<Schema>),
+    /// A set whose elements all match the inner schema.
+    Setof(Box<Schema>),
+    /// A dictionary whose keys and values all match the given schemas.
+    Dictof(Box<Schema>, Box<Schema>),
+    /// A dictionary with a fixed set of named, required keys. Each field's
+    /// matched value is captured in the resulting [`Bindings`] under its
+    /// name.
+    Dict(Vec<(String, Schema)>),
+    /// Matches if any of the alternatives match, in order.
+    Or(Vec<Schema>),
+    /// A reference to a schema registered in an [`Env`], allowing recursive
+    /// or mutually-recursive definitions.
+    Ref(String),
+}
+
+/// An environment of named schemas, resolved by [`Schema::Ref`].
+#[derive(Debug, Clone, Default)]
+pub struct Env {
+    definitions: HashMap<String, Schema>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env::default()
+    }
+
+    /// Register a named schema and return a [`Schema::Ref`] pointing to it,
+    /// so later definitions (or the schema itself) can refer back to it.
+    pub fn define<T: Into<String>>(&mut self, name: T, schema: Schema) -> Schema {
+        let name = name.into();
+        self.definitions.insert(name.clone(), schema);
+        Schema::Ref(name)
+    }
+
+    fn resolve(&self, name: &str) -> Option<&Schema> {
+        self.definitions.get(name)
+    }
+}
+
+/// One step toward the node where validation failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Field(usize),
+    DictKey(Value),
+}
+
+/// Why validation failed, and the path from the document root to the node
+/// that failed to match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub path: Vec<PathSegment>,
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new<T: Into<String>>(message: T) -> Self {
+        SchemaError {
+            path: vec![],
+            message: message.into(),
+        }
+    }
+
+    /// Prepend `segment` to the path, building it up from the point of
+    /// failure back out to the root as the error bubbles up the call stack.
+    fn at(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "at {:?}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// The named values captured while validating a document's [`Schema::Dict`]
+/// fields.
+pub type Bindings = HashMap<String, Value>;
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Boolean(_) => "a boolean",
+        Value::Float(_) => "a float",
+        Value::Double(_) => "a double",
+        Value::Integer(_) => "an integer",
+        Value::Binary(_) => "binary data",
+        Value::String(_) => "a string",
+        Value::Symbol(_) => "a symbol",
+        Value::Dictionary(_) => "a dictionary",
+        Value::Sequence(_) => "a sequence",
+        Value::Record { .. } => "a record",
+        Value::Set(_) => "a set",
+        Value::Embedded(_) => "an embedded value",
+    }
+}
+
+impl Schema {
+    /// Validate `value` against this schema, with no [`Env`] to resolve
+    /// [`Schema::Ref`]s (so the schema must not contain one).
+    pub fn validate(&self, value: &Value) -> std::result::Result<Bindings, SchemaError> {
+        self.validate_in(value, &Env::new())
+    }
+
+    /// Validate `value` against this schema, resolving any [`Schema::Ref`]
+    /// against `env`.
+    pub fn validate_in(
+        &self,
+        value: &Value,
+        env: &Env,
+    ) -> std::result::Result<Bindings, SchemaError> {
+        match self {
+            Schema::Atom(kind) => {
+                if kind.matches(value) {
+                    Ok(Bindings::new())
+                } else {
+                    Err(SchemaError::new(format!(
+                        "expected {:?}, found {}",
+                        kind,
+                        describe(value)
+                    )))
+                }
+            }
+            Schema::Record { label, fields } => match value {
+                Value::Record {
+                    label: actual_label,
+                    fields: actual_fields,
+                } => {
+                    if actual_label.as_ref() != label {
+                        return Err(SchemaError::new(format!(
+                            "expected record labeled {:?}, found {:?}",
+                            label, actual_label
+                        )));
+                    }
+                    if actual_fields.len() != fields.len() {
+                        return Err(SchemaError::new(format!(
+                            "expected {} fields, found {}",
+                            fields.len(),
+                            actual_fields.len()
+                        )));
+                    }
+                    let mut bindings = Bindings::new();
+                    for (i, (schema, actual)) in fields.iter().zip(actual_fields).enumerate() {
+                        bindings.extend(
+                            schema
+                                .validate_in(actual, env)
+                                .map_err(|e| e.at(PathSegment::Field(i)))?,
+                        );
+                    }
+                    Ok(bindings)
+                }
+                _ => Err(SchemaError::new(format!(
+                    "expected a record, found {}",
+                    describe(value)
+                ))),
+            },
+            Schema::Sequenceof(elem) => match value {
+                Value::Sequence(items) => validate_each(elem, items, env),
+                _ => Err(SchemaError::new(format!(
+                    "expected a sequence, found {}",
+                    describe(value)
+                ))),
+            },
+            Schema::Setof(elem) => match value {
+                Value::Set(items) => validate_each(elem, items, env),
+                _ => Err(SchemaError::new(format!(
+                    "expected a set, found {}",
+                    describe(value)
+                ))),
+            },
+            Schema::Dictof(key_schema, value_schema) => match value {
+                Value::Dictionary(entries) => {
+                    let mut bindings = Bindings::new();
+                    for (k, v) in entries {
+                        key_schema
+                            .validate_in(k, env)
+                            .map_err(|e| e.at(PathSegment::DictKey(k.clone())))?;
+                        bindings.extend(
+                            value_schema
+                                .validate_in(v, env)
+                                .map_err(|e| e.at(PathSegment::DictKey(k.clone())))?,
+                        );
+                    }
+                    Ok(bindings)
+                }
+                _ => Err(SchemaError::new(format!(
+                    "expected a dictionary, found {}",
+                    describe(value)
+                ))),
+            },
+            Schema::Dict(fields) => match value {
+                Value::Dictionary(entries) => {
+                    let mut bindings = Bindings::new();
+                    for (name, schema) in fields {
+                        let key = Value::symbol(name.as_str());
+                        match entries.iter().find(|(k, _)| k == &key) {
+                            Some((_, v)) => {
+                                bindings.extend(
+                                    schema
+                                        .validate_in(v, env)
+                                        .map_err(|e| e.at(PathSegment::DictKey(key.clone())))?,
+                                );
+                                bindings.insert(name.clone(), v.clone());
+                            }
+                            None => {
+                                return Err(SchemaError::new(format!(
+                                    "missing required key {:?}",
+                                    name
+                                ))
+                                .at(PathSegment::DictKey(key)));
+                            }
+                        }
+                    }
+                    Ok(bindings)
+                }
+                _ => Err(SchemaError::new(format!(
+                    "expected a dictionary, found {}",
+                    describe(value)
+                ))),
+            },
+            Schema::Or(alternatives) => {
+                let mut last_error = None;
+                for alternative in alternatives {
+                    match alternative.validate_in(value, env) {
+                        Ok(bindings) => return Ok(bindings),
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| SchemaError::new("no alternatives to match")))
+            }
+            Schema::Ref(name) => {
+                let resolved = env
+                    .resolve(name)
+                    .ok_or_else(|| SchemaError::new(format!("undefined schema ref {:?}", name)))?;
+                resolved.validate_in(value, env)
+            }
+        }
+    }
+}
+
+fn validate_each(
+    elem: &Schema,
+    items: &[Value],
+    env: &Env,
+) -> std::result::Result<Bindings, SchemaError> {
+    let mut bindings = Bindings::new();
+    for (i, item) in items.iter().enumerate() {
+        bindings.extend(
+            elem.validate_in(item, env)
+                .map_err(|e| e.at(PathSegment::Field(i)))?,
+        );
+    }
+    Ok(bindings)
+}
+
+#[test]
+fn test_atom_and_or() {
+    let schema = Schema::Or(vec![
+        Schema::Atom(AtomKind::Integer),
+        Schema::Atom(AtomKind::String),
+    ]);
+    assert!(schema.validate(&Value::integer(1)).is_ok());
+    assert!(schema.validate(&Value::string("x")).is_ok());
+    assert!(schema.validate(&Value::boolean(true)).is_err());
+}
+
+#[test]
+fn test_record_field_mismatch_reports_path() {
+    let schema = Schema::Record {
+        label: Value::symbol("point"),
+        fields: vec![
+            Schema::Atom(AtomKind::Integer),
+            Schema::Atom(AtomKind::Integer),
+        ],
+    };
+    let bad = Value::record(
+        Value::symbol("point"),
+        vec![Value::integer(1), Value::string("y")],
+    );
+    let err = schema.validate(&bad).unwrap_err();
+    assert_eq!(err.path, vec![PathSegment::Field(1)]);
+}
+
+#[test]
+fn test_dict_captures_bindings_and_rejects_missing_key() {
+    let schema = Schema::Dict(vec![
+        ("name".to_string(), Schema::Atom(AtomKind::String)),
+        ("age".to_string(), Schema::Atom(AtomKind::Integer)),
+    ]);
+
+    let animal = Value::dictionary(vec![
+        (Value::symbol("name"), Value::string("Tabatha")),
+        (Value::symbol("age"), Value::integer(12)),
+    ]);
+    let bindings = schema.validate(&animal).unwrap();
+    assert_eq!(bindings.get("name"), Some(&Value::string("Tabatha")));
+    assert_eq!(bindings.get("age"), Some(&Value::integer(12)));
+
+    let incomplete = Value::dictionary(vec![(Value::symbol("name"), Value::string("Tabatha"))]);
+    let err = schema.validate(&incomplete).unwrap_err();
+    assert_eq!(err.path, vec![PathSegment::DictKey(Value::symbol("age"))]);
+}
+
+#[test]
+fn test_ref_resolves_recursive_schema() {
+    let mut env = Env::new();
+    let list = env.define(
+        "list",
+        Schema::Or(vec![
+            Schema::Atom(AtomKind::Boolean),
+            Schema::Record {
+                label: Value::symbol("cons"),
+                fields: vec![
+                    Schema::Atom(AtomKind::Integer),
+                    Schema::Ref("list".to_string()),
+                ],
+            },
+        ]),
+    );
+
+    let doc = Value::record(
+        Value::symbol("cons"),
+        vec![
+            Value::integer(1),
+            Value::record(
+                Value::symbol("cons"),
+                vec![Value::integer(2), Value::boolean(false)],
+            ),
+        ],
+    );
+    assert!(list.validate_in(&doc, &env).is_ok());
+}
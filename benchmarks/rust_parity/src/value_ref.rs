@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    character::complete::digit1,
+    combinator::{consumed, map_res},
+    error::context,
+    multi::many_till,
+    sequence::{pair, preceded, terminated},
+    IResult,
+};
+use num_bigint::{BigInt, Sign};
+
+use crate::format::{Result, Value};
+
+/// A parsed syrup value that borrows its binary data, strings and symbols
+/// directly from the buffer it was parsed from, instead of copying them the
+/// way [`Value`] does.
+///
+/// Because Syrup length-prefixes every byte string, the parser can take the
+/// counted slice in one [`take`] rather than collecting it byte-by-byte,
+/// which makes `ValueRef` considerably cheaper to produce than `Value` for
+/// read-heavy workloads that only need to inspect a document rather than
+/// hold on to it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    Boolean(bool),
+    Float(f32),
+    Double(f64),
+    Integer(BigInt),
+    Binary(&'a [u8]),
+    /// Unlike [`Value::String`], this borrows directly from the input and so
+    /// requires the bytes to already be valid UTF-8; invalid UTF-8 fails to
+    /// parse rather than being lossily replaced.
+    String(&'a str),
+    Symbol(&'a str),
+    Dictionary(Vec<(Self, Self)>),
+    Sequence(Vec<Self>),
+    Record {
+        label: Box<Self>,
+        fields: Vec<Self>,
+    },
+    Set(Vec<Self>),
+    Embedded(&'a [u8]),
+}
+
+impl<'a> ValueRef<'a> {
+    /// Parse a single syrup value from the front of `input`, returning the
+    /// unconsumed remainder alongside it.
+    pub fn parse(input: &'a [u8]) -> Result<(&'a [u8], ValueRef<'a>)> {
+        value_ref(input).map_err(|e| crate::format::offset_error(input, e))
+    }
+
+    /// Copy this value's borrowed data out into an owned [`Value`].
+    pub fn to_owned(&self) -> Value {
+        match self {
+            ValueRef::Boolean(b) => Value::Boolean(*b),
+            ValueRef::Float(f) => Value::Float(*f),
+            ValueRef::Double(d) => Value::Double(*d),
+            ValueRef::Integer(i) => Value::Integer(i.clone()),
+            ValueRef::Binary(b) => Value::Binary(b.to_vec()),
+            ValueRef::String(s) => Value::String(s.to_string()),
+            ValueRef::Symbol(s) => Value::Symbol(s.to_string()),
+            ValueRef::Dictionary(d) => Value::Dictionary(
+                d.iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .collect(),
+            ),
+            ValueRef::Sequence(s) => Value::Sequence(s.iter().map(ValueRef::to_owned).collect()),
+            ValueRef::Record { label, fields } => Value::Record {
+                label: Box::new((**label).to_owned()),
+                fields: fields.iter().map(ValueRef::to_owned).collect(),
+            },
+            ValueRef::Set(s) => Value::Set(s.iter().map(ValueRef::to_owned).collect()),
+            ValueRef::Embedded(b) => Value::Embedded(b.to_vec()),
+        }
+    }
+}
+
+fn value_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context(
+        "value",
+        alt((
+            boolean_ref,
+            float_ref,
+            double_ref,
+            integer_ref,
+            binary_ref,
+            string_ref,
+            symbol_ref,
+            dictionary_ref,
+            sequence_ref,
+            record_ref,
+            set_ref,
+            embedded_ref,
+        )),
+    )(input)
+}
+
+fn boolean_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("boolean", alt((tag("t"), tag("f"))))(input).map(|(next_input, res)| {
+        (
+            next_input,
+            match res {
+                b"t" => ValueRef::Boolean(true),
+                b"f" => ValueRef::Boolean(false),
+                _ => unreachable!("parser"),
+            },
+        )
+    })
+}
+
+fn float_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("float", preceded(tag("F"), take(4u8)))(input).map(|(next_input, res)| {
+        (
+            next_input,
+            ValueRef::Float(f32::from_be_bytes(res.try_into().unwrap())),
+        )
+    })
+}
+
+fn double_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("double", preceded(tag("D"), take(8u8)))(input).map(|(next_input, res)| {
+        (
+            next_input,
+            ValueRef::Double(f64::from_be_bytes(res.try_into().unwrap())),
+        )
+    })
+}
+
+fn integer_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("integer", pair(digit1, alt((tag("+"), tag("-")))))(input).map(|(next_input, res)| {
+        let (num_str, sign_str) = res;
+        let sign = match sign_str {
+            b"+" => Sign::Plus,
+            b"-" => Sign::Minus,
+            _ => unreachable!(),
+        };
+        (
+            next_input,
+            ValueRef::Integer(
+                BigInt::from_radix_be(
+                    sign,
+                    num_str
+                        .iter()
+                        .map(|d| d - 0x30)
+                        .collect::<Vec<u8>>()
+                        .as_slice(),
+                    10,
+                )
+                .unwrap(),
+            ),
+        )
+    })
+}
+
+/// Parse a Syrup length prefix (`n:` / `n"` / `n'` / `n&`) terminated by `tag_str`, then
+/// take exactly that many bytes in one shot instead of collecting them
+/// byte-by-byte.
+fn counted_slice<'a>(tag_str: &'static str) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], &'a [u8]> {
+    move |input| {
+        let (input, len) = terminated(
+            map_res(digit1, |d: &[u8]| {
+                std::str::from_utf8(d)
+                    .ok()
+                    .and_then(|s| u32::from_str(s).ok())
+                    .ok_or(())
+            }),
+            tag(tag_str),
+        )(input)?;
+        take(len)(input)
+    }
+}
+
+fn binary_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("binary", counted_slice(":"))(input)
+        .map(|(next_input, bytes)| (next_input, ValueRef::Binary(bytes)))
+}
+
+fn embedded_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("embedded", counted_slice("&"))(input)
+        .map(|(next_input, bytes)| (next_input, ValueRef::Embedded(bytes)))
+}
+
+fn string_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("string", map_res(counted_slice("\""), std::str::from_utf8))(input)
+        .map(|(next_input, s)| (next_input, ValueRef::String(s)))
+}
+
+fn symbol_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context("symbol", map_res(counted_slice("'"), std::str::from_utf8))(input)
+        .map(|(next_input, s)| (next_input, ValueRef::Symbol(s)))
+}
+
+fn sequence_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context(
+        "sequence",
+        preceded(tag("["), many_till(value_ref, tag("]"))),
+    )(input)
+    .map(|(next_input, res)| (next_input, ValueRef::Sequence(res.0)))
+}
+
+fn dictionary_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context(
+        "dictionary",
+        preceded(
+            tag("{"),
+            many_till(consumed(pair(value_ref, value_ref)), tag("}")),
+        ),
+    )(input)
+    .map(|(next_input, (mut entries, _))| {
+        // Sort by the exact bytes each entry was parsed from: that's the
+        // same bytewise order `Value`'s canonical form sorts by, without
+        // needing to re-encode anything.
+        entries.sort_by_key(|(span, _)| *span);
+        (
+            next_input,
+            ValueRef::Dictionary(entries.into_iter().map(|(_, kv)| kv).collect()),
+        )
+    })
+}
+
+fn record_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context(
+        "record",
+        preceded(tag("<"), pair(value_ref, many_till(value_ref, tag(">")))),
+    )(input)
+    .map(|(next_input, (label, (fields, _)))| {
+        (
+            next_input,
+            ValueRef::Record {
+                label: Box::new(label),
+                fields,
+            },
+        )
+    })
+}
+
+fn set_ref(input: &[u8]) -> IResult<&[u8], ValueRef<'_>> {
+    context(
+        "set",
+        preceded(tag("#"), many_till(consumed(value_ref), tag("$"))),
+    )(input)
+    .map(|(next_input, (mut elements, _))| {
+        elements.sort_by_key(|(span, _)| *span);
+        (
+            next_input,
+            ValueRef::Set(elements.into_iter().map(|(_, v)| v).collect()),
+        )
+    })
+}
+
+#[test]
+fn test_parse_matches_value() {
+    for s in [
+        "t",
+        "f",
+        "10+",
+        "10-",
+        "5:hello",
+        "3\"foo",
+        "4'none",
+        "[1+2+3+]",
+        "{3\"foo3\"bar3\"goo4\"muck}",
+        "<6:person5:Alice30+t>",
+        "#3\"bar3\"foo$",
+        "5&hello",
+    ] {
+        let expected = Value::from_str(s).unwrap();
+        let (remaining, parsed) = ValueRef::parse(s.as_bytes()).unwrap();
+        assert!(remaining.is_empty());
+        assert_eq!(parsed.to_owned(), expected, "parse: {}", s);
+    }
+}
+
+#[test]
+fn test_parse_rejects_oversized_length_prefix() {
+    // A length prefix that overflows u32 must fail to parse rather than
+    // panicking, since this is the default decode path for untrusted input.
+    assert!(ValueRef::parse(b"99999999999999999999:hello").is_err());
+}
+
+#[test]
+fn test_parse_borrows_without_copying() {
+    let input = b"5:hello".as_slice();
+    let (remaining, parsed) = ValueRef::parse(input).unwrap();
+    assert!(remaining.is_empty());
+    match parsed {
+        ValueRef::Binary(b) => {
+            // The borrowed slice should point straight into `input`, not a
+            // freshly allocated copy.
+            assert_eq!(b.as_ptr(), input[2..].as_ptr());
+        }
+        other => panic!("expected ValueRef::Binary, got {:?}", other),
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::Write as IoWrite;
+
+use num_bigint::Sign;
+
+use crate::format::{Result, Value};
+
+/// Streams a [`Value`] straight to a `std::io::Write` sink, one token at a
+/// time, instead of building it up as a tree of nested `Vec<u8>`s the way
+/// [`Value::to_vec`] does. Useful for encoding documents too large to hold
+/// fully materialized, or for writing straight to a socket.
+pub struct Writer<W> {
+    output: W,
+}
+
+impl<W: IoWrite> Writer<W> {
+    pub fn new(output: W) -> Self {
+        Writer { output }
+    }
+
+    /// Recover the underlying sink.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    /// Write `value` to the sink in its binary on-the-wire representation.
+    pub fn write_value(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Boolean(true) => self.output.write_all(b"t")?,
+            Value::Boolean(false) => self.output.write_all(b"f")?,
+            Value::Float(f) => {
+                self.output.write_all(b"F")?;
+                self.output.write_all(&f.to_be_bytes())?;
+            }
+            Value::Double(d) => {
+                self.output.write_all(b"D")?;
+                self.output.write_all(&d.to_be_bytes())?;
+            }
+            Value::Integer(i) => {
+                let suffix = if i.sign() == Sign::Minus { "-" } else { "+" };
+                write!(self.output, "{}{}", i.magnitude().to_str_radix(10), suffix)?;
+            }
+            Value::Binary(b) => {
+                write!(self.output, "{}:", b.len())?;
+                self.output.write_all(b)?;
+            }
+            Value::String(s) => {
+                write!(self.output, "{}\"", s.as_bytes().len())?;
+                self.output.write_all(s.as_bytes())?;
+            }
+            Value::Symbol(s) => {
+                write!(self.output, "{}'", s.as_bytes().len())?;
+                self.output.write_all(s.as_bytes())?;
+            }
+            Value::Sequence(items) => {
+                self.output.write_all(b"[")?;
+                for item in items {
+                    self.write_value(item)?;
+                }
+                self.output.write_all(b"]")?;
+            }
+            Value::Dictionary(entries) => {
+                self.output.write_all(b"{")?;
+                for (k, v) in entries {
+                    self.write_value(k)?;
+                    self.write_value(v)?;
+                }
+                self.output.write_all(b"}")?;
+            }
+            Value::Record { label, fields } => {
+                self.output.write_all(b"<")?;
+                self.write_value(label)?;
+                for field in fields {
+                    self.write_value(field)?;
+                }
+                self.output.write_all(b">")?;
+            }
+            Value::Set(items) => {
+                self.output.write_all(b"#")?;
+                for item in items {
+                    self.write_value(item)?;
+                }
+                self.output.write_all(b"$")?;
+            }
+            Value::Embedded(b) => {
+                write!(self.output, "{}&", b.len())?;
+                self.output.write_all(b)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_value_matches_to_vec() {
+    let value = Value::record(
+        Value::binary(b"person".as_slice()),
+        vec![
+            Value::string("Alice"),
+            Value::integer(30),
+            Value::boolean(true),
+            Value::sequence(vec![Value::integer(1), Value::integer(2)]),
+            Value::dictionary(vec![(Value::symbol("k"), Value::string("v"))]),
+            Value::set(vec![Value::symbol("a"), Value::symbol("b")]),
+        ],
+    );
+
+    let mut buf = vec![];
+    Writer::new(&mut buf).write_value(&value).unwrap();
+    assert_eq!(buf, value.to_vec());
+}